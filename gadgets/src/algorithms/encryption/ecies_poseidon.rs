@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with the snarkVM library. If not, see <https://www.gnu.org/licenses/>.
 
+use super::scalar::CanonicalScalarGadget;
 use crate::{
     algorithms::crypto_hash::{CryptographicSpongeVar, PoseidonSpongeGadget},
     AllocGadget,
@@ -188,9 +189,101 @@ where
 )]
 pub struct ECIESPoseidonEncryptionGadget<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField> {
     encryption: ECIESPoseidonEncryption<TE>,
+    /// An optional domain separator, absorbed into the sponge before the ECDH x-coordinate, so
+    /// that a single key can be safely reused across distinct ciphertext channels without their
+    /// keystreams colliding.
+    domain: Option<Vec<u8>>,
+    /// The Poseidon sponge rate used by `check_encryption_gadget`. Defaults to `4`, matching the
+    /// rate `ECIESPoseidonEncryption` has always used.
+    rate: usize,
     f_phantom: PhantomData<F>,
 }
 
+impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField> ECIESPoseidonEncryptionGadget<TE, F> {
+    /// The sponge rate `check_encryption_gadget` falls back to when none is configured.
+    const DEFAULT_RATE: usize = 4;
+
+    /// Returns a copy of `self` that absorbs `domain` before the ECDH x-coordinate, binding the
+    /// resulting keystream to an application context.
+    pub fn with_domain(mut self, domain: Vec<u8>) -> Self {
+        self.domain = Some(domain);
+        self
+    }
+
+    /// Returns a copy of `self` configured to use the given Poseidon sponge rate.
+    pub fn with_rate(mut self, rate: usize) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    /// Packs `bits` into `TE::BaseField` elements, `CAPACITY` bits at a time, little-endian.
+    fn pack_bits_into_field_elements<CS: ConstraintSystem<F>>(
+        mut cs: CS,
+        bits: &[Boolean],
+    ) -> Result<Vec<FpGadget<F>>, SynthesisError> {
+        let capacity = <<F as PrimeField>::Parameters as FieldParameters>::CAPACITY as usize;
+        let mut res = Vec::with_capacity((bits.len() + capacity - 1) / capacity);
+        for (i, chunk) in bits.chunks(capacity).enumerate() {
+            let mut sum = FpGadget::<F>::zero(cs.ns(|| format!("field zero {}", i)))?;
+
+            let mut cur = F::one();
+            for (j, bit) in chunk.iter().enumerate() {
+                let add =
+                    FpGadget::from_boolean(cs.ns(|| format!("convert a bit to a field element {} {}", i, j)), *bit)?
+                        .mul_by_constant(cs.ns(|| format!("multiply by the shift {} {}", i, j)), &cur)?;
+                sum.add_in_place(
+                    cs.ns(|| format!("assemble the bit result into field elements {} {}", i, j)),
+                    &add,
+                )?;
+
+                cur.double_in_place();
+            }
+
+            res.push(sum);
+        }
+
+        Ok(res)
+    }
+
+    /// Recovers the `num_bits` least-significant bits of `elem`, little-endian, and enforces
+    /// that packing them back together the same way `pack_bits_into_field_elements` does
+    /// reproduces `elem`. This is the exact inverse of `pack_bits_into_field_elements`'s
+    /// innermost loop, so it does not depend on the bit order of any generic `to_bits` gadget.
+    fn unpack_field_element_into_bits<CS: ConstraintSystem<F>>(
+        mut cs: CS,
+        elem: &FpGadget<F>,
+        num_bits: usize,
+    ) -> Result<Vec<Boolean>, SynthesisError> {
+        let bit_values = match elem.get_value() {
+            Some(value) => {
+                let repr_bits = value.to_repr().to_bits_le();
+                (0..num_bits).map(|i| Some(repr_bits[i])).collect::<Vec<_>>()
+            }
+            None => vec![None; num_bits],
+        };
+
+        let bits = bit_values
+            .into_iter()
+            .enumerate()
+            .map(|(i, bit)| {
+                Boolean::alloc(cs.ns(|| format!("alloc bit {}", i)), || bit.ok_or(SynthesisError::AssignmentMissing))
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+        let mut sum = FpGadget::<F>::zero(cs.ns(|| "zero"))?;
+        let mut cur = F::one();
+        for (i, bit) in bits.iter().enumerate() {
+            let add = FpGadget::from_boolean(cs.ns(|| format!("convert bit {}", i)), *bit)?
+                .mul_by_constant(cs.ns(|| format!("multiply by the shift {}", i)), &cur)?;
+            sum.add_in_place(cs.ns(|| format!("assemble bit {}", i)), &add)?;
+            cur.double_in_place();
+        }
+        sum.enforce_equal(cs.ns(|| "enforce packed bits equal element"), elem)?;
+
+        Ok(bits)
+    }
+}
+
 impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField> AllocGadget<ECIESPoseidonEncryption<TE>, F>
     for ECIESPoseidonEncryptionGadget<TE, F>
 {
@@ -204,6 +297,8 @@ impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField> AllocGadget<ECI
     ) -> Result<Self, SynthesisError> {
         Ok(Self {
             encryption: (*value_gen()?.borrow()).clone(),
+            domain: None,
+            rate: Self::DEFAULT_RATE,
             f_phantom: PhantomData,
         })
     }
@@ -334,7 +429,10 @@ impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField + PoseidonDefaul
         mut cs: CS,
         private_key: &Self::PrivateKeyGadget,
     ) -> Result<Self::PublicKeyGadget, SynthesisError> {
-        let private_key_bits = private_key.0.iter().flat_map(|b| b.to_bits_le()).collect::<Vec<_>>();
+        // Range-check the private key against `TE::ScalarField`'s modulus before it is ever
+        // multiplied against the generator, so only canonical scalars produce a public key.
+        let private_key_bits =
+            CanonicalScalarGadget::<TE, F>::alloc(cs.ns(|| "canonicalize private key"), &private_key.0)?.bits;
         let mut public_key = <TEAffineGadget<TE, F> as GroupGadget<TEAffine<TE>, F>>::zero(cs.ns(|| "zero"))?;
 
         let num_powers = private_key_bits.len();
@@ -367,10 +465,11 @@ impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField + PoseidonDefaul
     ) -> Result<Vec<UInt8>, SynthesisError> {
         let affine_zero: TEAffineGadget<TE, F> =
             <TEAffineGadget<TE, F> as GroupGadget<TEAffine<TE>, F>>::zero(cs.ns(|| "affine zero")).unwrap();
-        let group_zero = FpGadget::<TE::BaseField>::zero(cs.ns(|| "field zero"))?;
 
-        // Compute the ECDH value.
-        let randomness_bits = randomness.0.iter().flat_map(|b| b.to_bits_le()).collect::<Vec<_>>();
+        // Compute the ECDH value. The randomness scalar is range-checked for the same reason
+        // the private key is in `check_public_key_gadget`.
+        let randomness_bits =
+            CanonicalScalarGadget::<TE, F>::alloc(cs.ns(|| "canonicalize randomness"), &randomness.0)?.bits;
         let ecdh_value = <TEAffineGadget<TE, F> as GroupGadget<TEAffine<TE>, F>>::mul_bits(
             &public_key.0,
             cs.ns(|| "compute_ecdh_value"),
@@ -378,10 +477,24 @@ impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField + PoseidonDefaul
             randomness_bits.iter().copied(),
         )?;
 
-        // Prepare the sponge.
-        let params =
-            <TE::BaseField as PoseidonDefaultParametersField>::get_default_poseidon_parameters(4, false).unwrap();
+        // Prepare the sponge, using this gadget's configured rate.
+        let params = <TE::BaseField as PoseidonDefaultParametersField>::get_default_poseidon_parameters(
+            self.rate, false,
+        )
+        .unwrap();
         let mut sponge = PoseidonSpongeGadget::<TE::BaseField>::new(cs.ns(|| "sponge"), &params);
+
+        // Bind the keystream to an application context before absorbing the ECDH point, so that
+        // distinct message types reusing the same key cannot collide.
+        if let Some(domain) = &self.domain {
+            let domain_bits = domain
+                .iter()
+                .flat_map(|byte| UInt8::constant(*byte).to_bits_le())
+                .collect::<Vec<_>>();
+            let domain_field_elements =
+                Self::pack_bits_into_field_elements(cs.ns(|| "pack domain separator"), &domain_bits)?;
+            sponge.absorb(cs.ns(|| "absorb domain separator"), domain_field_elements.iter())?;
+        }
         sponge.absorb(cs.ns(|| "absorb"), [ecdh_value.x].iter())?;
 
         // Convert the message into bits.
@@ -394,26 +507,7 @@ impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField + PoseidonDefaul
         // make sure that the length is correct.
 
         // Pack the bits into field elements.
-        let capacity = <<TE::BaseField as PrimeField>::Parameters as FieldParameters>::CAPACITY as usize;
-        let mut res = Vec::with_capacity((bits.len() + capacity - 1) / capacity);
-        for (i, chunk) in bits.chunks(capacity).enumerate() {
-            let mut sum = group_zero.clone();
-
-            let mut cur = TE::BaseField::one();
-            for (j, bit) in chunk.iter().enumerate() {
-                let add =
-                    FpGadget::from_boolean(cs.ns(|| format!("convert a bit to a field element {} {}", i, j)), *bit)?
-                        .mul_by_constant(cs.ns(|| format!("multiply by the shift {} {}", i, j)), &cur)?;
-                sum.add_in_place(
-                    cs.ns(|| format!("assemble the bit result into field elements {} {}", i, j)),
-                    &add,
-                )?;
-
-                cur.double_in_place();
-            }
-
-            res.push(sum);
-        }
+        let mut res = Self::pack_bits_into_field_elements(cs.ns(|| "pack message"), &bits)?;
 
         // Obtain random field elements from Poseidon.
         let sponge_field_elements =
@@ -446,4 +540,415 @@ impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField + PoseidonDefaul
 
         Ok([random_elem_bytes, res_bytes].concat())
     }
+
+    fn check_decryption_gadget<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+        private_key: &Self::PrivateKeyGadget,
+        ciphertext: &[UInt8],
+    ) -> Result<Vec<UInt8>, SynthesisError> {
+        let affine_zero: TEAffineGadget<TE, F> =
+            <TEAffineGadget<TE, F> as GroupGadget<TEAffine<TE>, F>>::zero(cs.ns(|| "affine zero"))?;
+
+        // The ciphertext starts with the bytes of the x-coordinate of the randomness group
+        // element, followed by the masked field elements, mirroring `check_encryption_gadget`.
+        let field_byte_size =
+            (<<TE::BaseField as PrimeField>::Parameters as FieldParameters>::MODULUS_BITS as usize + 7) / 8;
+        if ciphertext.len() < field_byte_size {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let (x_bytes, masked_bytes) = ciphertext.split_at(field_byte_size);
+        // The masked region must be a whole number of field-element chunks; otherwise the last
+        // chunk below would be shorter than `field_byte_size` and this is a malformed ciphertext
+        // to reject, not a case to let `zip_eq` panic on.
+        if masked_bytes.len() % field_byte_size != 0 {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        // Recover the x-coordinate of the randomness group element from its bytes.
+        let x_value = x_bytes
+            .iter()
+            .map(|byte| byte.get_value())
+            .collect::<Option<Vec<_>>>()
+            .map(|bytes| F::read_le(&bytes[..]))
+            .transpose()
+            .map_err(|_| SynthesisError::Unsatisfiable)?;
+        let x = FpGadget::<F>::alloc(cs.ns(|| "alloc randomness x"), || {
+            x_value.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let x_bytes_check = x.to_bytes(cs.ns(|| "randomness x to bytes"))?;
+        for (i, (computed, given)) in x_bytes_check.iter().zip_eq(x_bytes).enumerate() {
+            computed.enforce_equal(cs.ns(|| format!("check randomness x byte {}", i)), given)?;
+        }
+
+        // Witness the y-coordinate together with a parity bit that pins down which of the two
+        // square roots completes the point, then enforce the twisted-Edwards curve equation
+        // `a * x^2 + y^2 = 1 + d * x^2 * y^2` so the recovered point is actually on the curve.
+        let (y_value, y_is_odd_value) = match x.get_value() {
+            Some(x_val) => {
+                let x_squared = x_val.square();
+                let numerator = F::one() - (TE::COEFF_A * x_squared);
+                let denominator = F::one() - (TE::COEFF_D * x_squared);
+                let y_squared = numerator * denominator.inverse().ok_or(SynthesisError::Unsatisfiable)?;
+                let y = y_squared.sqrt().ok_or(SynthesisError::Unsatisfiable)?;
+                (Some(y), Some(y.to_repr().is_odd()))
+            }
+            None => (None, None),
+        };
+        let y = FpGadget::<F>::alloc(cs.ns(|| "alloc randomness y"), || {
+            y_value.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let y_is_odd = Boolean::alloc(cs.ns(|| "alloc randomness y parity"), || {
+            y_is_odd_value.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let x_squared = x.mul(cs.ns(|| "x^2"), &x)?;
+        let y_squared = y.mul(cs.ns(|| "y^2"), &y)?;
+        let x_squared_y_squared = x_squared.mul(cs.ns(|| "x^2 * y^2"), &y_squared)?;
+
+        let lhs = x_squared
+            .mul_by_constant(cs.ns(|| "a * x^2"), &TE::COEFF_A)?
+            .add(cs.ns(|| "a * x^2 + y^2"), &y_squared)?;
+        let rhs = x_squared_y_squared
+            .mul_by_constant(cs.ns(|| "d * x^2 * y^2"), &TE::COEFF_D)?
+            .add_constant(cs.ns(|| "1 + d * x^2 * y^2"), &F::one())?;
+        lhs.enforce_equal(cs.ns(|| "enforce randomness point is on curve"), &rhs)?;
+
+        // Select the root that matches the witnessed parity, so the ECDH computation below
+        // lands on the same shared point the encryptor used.
+        let negated_y = y.negate(cs.ns(|| "negate y"))?;
+        let y = FpGadget::<F>::conditionally_select(cs.ns(|| "select y by parity"), &y_is_odd, &negated_y, &y)?;
+
+        let randomness_point = TEAffineGadget::<TE, F>::new(x, y);
+
+        // Compute the ECDH value. The private key is range-checked for the same reason it is in
+        // `check_public_key_gadget`, so decryption cannot be fed a non-canonical scalar either.
+        let private_key_bits =
+            CanonicalScalarGadget::<TE, F>::alloc(cs.ns(|| "canonicalize private key"), &private_key.0)?.bits;
+        let ecdh_value = <TEAffineGadget<TE, F> as GroupGadget<TEAffine<TE>, F>>::mul_bits(
+            &randomness_point,
+            cs.ns(|| "compute_ecdh_value"),
+            &affine_zero,
+            private_key_bits.iter().copied(),
+        )?;
+
+        // Prepare the sponge, matching the rate and domain separation used during encryption.
+        let params = <TE::BaseField as PoseidonDefaultParametersField>::get_default_poseidon_parameters(
+            self.rate, false,
+        )
+        .unwrap();
+        let mut sponge = PoseidonSpongeGadget::<TE::BaseField>::new(cs.ns(|| "sponge"), &params);
+        if let Some(domain) = &self.domain {
+            let domain_bits = domain
+                .iter()
+                .flat_map(|byte| UInt8::constant(*byte).to_bits_le())
+                .collect::<Vec<_>>();
+            let domain_field_elements =
+                Self::pack_bits_into_field_elements(cs.ns(|| "pack domain separator"), &domain_bits)?;
+            sponge.absorb(cs.ns(|| "absorb domain separator"), domain_field_elements.iter())?;
+        }
+        sponge.absorb(cs.ns(|| "absorb"), [ecdh_value.x].iter())?;
+
+        // Recover the masked field elements from the remaining ciphertext bytes.
+        let masked_field_elements = masked_bytes
+            .chunks(field_byte_size)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let value = chunk
+                    .iter()
+                    .map(|byte| byte.get_value())
+                    .collect::<Option<Vec<_>>>()
+                    .map(|bytes| F::read_le(&bytes[..]))
+                    .transpose()
+                    .map_err(|_| SynthesisError::Unsatisfiable)?;
+                let elem = FpGadget::<F>::alloc(cs.ns(|| format!("alloc masked element {}", i)), || {
+                    value.ok_or(SynthesisError::AssignmentMissing)
+                })?;
+                let elem_bytes = elem.to_bytes(cs.ns(|| format!("masked element {} to bytes", i)))?;
+                for (j, (computed, given)) in elem_bytes.iter().zip_eq(chunk).enumerate() {
+                    computed.enforce_equal(cs.ns(|| format!("check masked element {} byte {}", i, j)), given)?;
+                }
+                Ok(elem)
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+        // Squeeze the same number of masking field elements Poseidon produced during
+        // encryption, and subtract them back out.
+        let sponge_field_elements =
+            sponge.squeeze_field_elements(cs.ns(|| "squeeze for random elements"), masked_field_elements.len())?;
+
+        let capacity = <<TE::BaseField as PrimeField>::Parameters as FieldParameters>::CAPACITY as usize;
+        let mut bits = Vec::with_capacity(masked_field_elements.len() * capacity);
+        for (i, (masked, mask)) in masked_field_elements.iter().zip_eq(&sponge_field_elements).enumerate() {
+            let negated_mask = mask.negate(cs.ns(|| format!("negate mask {}", i)))?;
+            let unmasked = masked.add(cs.ns(|| format!("unmask element {}", i)), &negated_mask)?;
+            // Recover the bits by mirroring `pack_bits_into_field_elements` exactly (least
+            // significant bit first, the same way `cur` is built up there) and enforcing that
+            // re-packing them reproduces `unmasked`, rather than trusting the bit order of a
+            // generic `to_bits`-style call.
+            let unmasked_bits =
+                Self::unpack_field_element_into_bits(cs.ns(|| format!("unpack element {}", i)), &unmasked, capacity)?;
+            bits.extend_from_slice(&unmasked_bits);
+        }
+
+        // Pad to a byte boundary with structural (non-witnessed) zero bits, so the recovered
+        // plaintext buffer below always has a length determined solely by the ciphertext size,
+        // never by the witnessed message content.
+        while bits.len() % 8 != 0 {
+            bits.push(Boolean::constant(false));
+        }
+        let total_bits = bits.len();
+
+        // Locate the `Boolean::Constant(true)` end-marker bit the encoder appended, as the
+        // rightmost set bit, using only boolean constraints (no native `get_value` branching) so
+        // this synthesizes identically whether or not a witness is present.
+        let mut found_after = vec![Boolean::constant(false); total_bits + 1];
+        for i in (0..total_bits).rev() {
+            found_after[i] =
+                Boolean::or(cs.ns(|| format!("found marker after {}", i)), &found_after[i + 1], &bits[i])?;
+        }
+        let mut is_marker = Vec::with_capacity(total_bits);
+        for i in 0..total_bits {
+            let marker_i = Boolean::and(
+                cs.ns(|| format!("is end marker {}", i)),
+                &bits[i],
+                &found_after[i + 1].not(),
+            )?;
+            is_marker.push(marker_i);
+        }
+
+        // Reject ciphertexts that carry no end marker at all.
+        found_after[0].enforce_equal(cs.ns(|| "enforce end marker is present"), &Boolean::constant(true))?;
+
+        // Reject ciphertexts whose end marker does not land on a byte boundary, since the encoder
+        // only ever places it right after a whole number of message bytes.
+        for (i, marker_i) in is_marker.iter().enumerate() {
+            if i % 8 != 0 {
+                marker_i.enforce_equal(
+                    cs.ns(|| format!("enforce marker {} is byte-aligned", i)),
+                    &Boolean::constant(false),
+                )?;
+            }
+        }
+
+        // Zero everything at or after the marker, producing a fixed-length, zero-padded
+        // plaintext buffer whose size depends only on the (public) ciphertext length.
+        let mut seen_marker = Boolean::constant(false);
+        let mut output_bits = Vec::with_capacity(total_bits);
+        for i in 0..total_bits {
+            let still_active = Boolean::and(cs.ns(|| format!("still active {}", i)), &seen_marker.not(), &is_marker[i].not())?;
+            let output_bit = Boolean::and(cs.ns(|| format!("plaintext bit {}", i)), &bits[i], &still_active)?;
+            output_bits.push(output_bit);
+            seen_marker = Boolean::or(cs.ns(|| format!("accumulate seen marker {}", i)), &seen_marker, &is_marker[i])?;
+        }
+
+        let plaintext = output_bits.chunks(8).map(UInt8::from_bits_le).collect::<Vec<_>>();
+
+        Ok(plaintext)
+    }
+}
+
+impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField + PoseidonDefaultParametersField>
+    ECIESPoseidonEncryptionGadget<TE, F>
+{
+    /// Window size (in bits) used by `check_public_key_gadget_windowed`'s fixed-base lookup
+    /// tables. Doubling it halves the number of windows (and so the number of table selections
+    /// and point additions), at the cost of quadrupling the size of each lookup table.
+    const FIXED_BASE_WINDOW_SIZE: usize = 2;
+
+    /// A windowed fixed-base variant of `check_public_key_gadget`. Since the generator is a
+    /// known constant (`self.encryption.generator`), the bit-by-bit doubling-and-add of
+    /// `check_public_key_gadget` (one conditional addition per scalar bit) can be replaced with
+    /// lookup tables of `[k * (2^(w*i)) * G]`, for `k` in `0..2^w`, precomputed out-of-circuit
+    /// for each window `i`. In-circuit, the correct table entry is selected per window using the
+    /// scalar's window bits and accumulated with `add`, dropping the constraint count from
+    /// ~1 add/bit to ~1 add/window plus table-selection constraints.
+    pub fn check_public_key_gadget_windowed<CS: ConstraintSystem<TE::BaseField>>(
+        &self,
+        mut cs: CS,
+        private_key: &ECIESPoseidonEncryptionPrivateKeyGadget<TE, F>,
+    ) -> Result<ECIESPoseidonEncryptionPublicKeyGadget<TE, F>, SynthesisError> {
+        let scalar_bits = CanonicalScalarGadget::<TE, F>::alloc(
+            cs.ns(|| "canonicalize private key"),
+            &private_key.0,
+        )?
+        .bits;
+
+        let window_size = Self::FIXED_BASE_WINDOW_SIZE;
+        let table_size = 1usize << window_size;
+
+        // Precompute, out-of-circuit, one lookup table per window: `tables[i][k] = k * (2^(w*i)) * G`.
+        let tables: Vec<Vec<TEAffine<TE>>> = {
+            let mut tables = Vec::with_capacity((scalar_bits.len() + window_size - 1) / window_size);
+            let mut window_base = self.encryption.generator.into_projective();
+            for _ in (0..scalar_bits.len()).step_by(window_size) {
+                let mut table = Vec::with_capacity(table_size);
+                let mut entry = <TEProjective<TE> as Group>::zero();
+                for _ in 0..table_size {
+                    table.push(entry.clone());
+                    entry += &window_base;
+                }
+                TEProjective::<TE>::batch_normalization(&mut table);
+                tables.push(table.into_iter().map(|v| v.into()).collect());
+                for _ in 0..window_size {
+                    window_base.double_in_place();
+                }
+            }
+            tables
+        };
+
+        let mut public_key = <TEAffineGadget<TE, F> as GroupGadget<TEAffine<TE>, F>>::zero(cs.ns(|| "zero"))?;
+        for (i, (window_bits, table)) in scalar_bits.chunks(window_size).zip_eq(&tables).enumerate() {
+            let selected =
+                Self::select_table_entry(cs.ns(|| format!("select window {}", i)), window_bits, table)?;
+            public_key = public_key.add(cs.ns(|| format!("accumulate window {}", i)), &selected)?;
+        }
+
+        Ok(ECIESPoseidonEncryptionPublicKeyGadget::<TE, F> { 0: public_key })
+    }
+
+    /// Selects `table[index]`, where `index`'s little-endian bits are `selector_bits`, via a
+    /// binary tree of conditional selects over the constant table entries.
+    fn select_table_entry<CS: ConstraintSystem<TE::BaseField>>(
+        mut cs: CS,
+        selector_bits: &[Boolean],
+        table: &[TEAffine<TE>],
+    ) -> Result<TEAffineGadget<TE, F>, SynthesisError> {
+        // The final window may have fewer real scalar bits than `FIXED_BASE_WINDOW_SIZE` (when
+        // the scalar's bit length isn't a multiple of the window size); those missing bits are
+        // always the scalar's highest-order bits, which are implicitly zero. Pad with constant
+        // `false` bits so the reduction below always walks exactly `log2(table.len())` levels,
+        // rather than stopping early on a level with more than one remaining entry.
+        let window_size = table.len().trailing_zeros() as usize;
+        let mut selector_bits = selector_bits.to_vec();
+        selector_bits.resize(window_size, Boolean::constant(false));
+
+        let mut level = table
+            .iter()
+            .enumerate()
+            .map(|(i, point)| {
+                TEAffineGadget::<TE, F>::alloc_constant(cs.ns(|| format!("alloc table entry {}", i)), || {
+                    Ok(point.clone())
+                })
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+        for (depth, bit) in selector_bits.iter().enumerate() {
+            let mut next_level = Vec::with_capacity(level.len() / 2);
+            for (i, pair) in level.chunks(2).enumerate() {
+                let selected = TEAffineGadget::<TE, F>::conditionally_select(
+                    cs.ns(|| format!("select depth {} pair {}", depth, i)),
+                    bit,
+                    &pair[1],
+                    &pair[0],
+                )?;
+                next_level.push(selected);
+            }
+            level = next_level;
+        }
+
+        Ok(level.remove(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm_algorithms::EncryptionScheme;
+    use snarkvm_curves::edwards_bls12::{EdwardsParameters, Fq};
+    use snarkvm_r1cs::TestConstraintSystem;
+    use snarkvm_utilities::test_rng;
+
+    #[test]
+    fn test_encryption_gadget_encrypt_then_decrypt() {
+        let rng = &mut test_rng();
+
+        let encryption = ECIESPoseidonEncryption::<EdwardsParameters>::setup("test_encryption_gadget_encrypt_then_decrypt");
+        let private_key = encryption.generate_private_key(rng);
+        let public_key = encryption.generate_public_key(&private_key);
+        let randomness = encryption.generate_randomness(rng);
+        let message = b"the quick brown fox jumps over the lazy dog".to_vec();
+
+        let mut cs = TestConstraintSystem::<Fq>::new();
+
+        let encryption_gadget = ECIESPoseidonEncryptionGadget::<EdwardsParameters, Fq>::alloc_constant(
+            cs.ns(|| "alloc encryption scheme"),
+            || Ok(encryption.clone()),
+        )
+        .unwrap();
+        let private_key_gadget = ECIESPoseidonEncryptionPrivateKeyGadget::<EdwardsParameters, Fq>::alloc(
+            cs.ns(|| "alloc private key"),
+            || Ok(private_key),
+        )
+        .unwrap();
+        let randomness_gadget =
+            ECIESPoseidonEncryptionRandomnessGadget::<EdwardsParameters>::alloc(cs.ns(|| "alloc randomness"), || {
+                Ok(randomness)
+            })
+            .unwrap();
+        let public_key_gadget = ECIESPoseidonEncryptionPublicKeyGadget::<EdwardsParameters, Fq>::alloc(
+            cs.ns(|| "alloc public key"),
+            || Ok(public_key),
+        )
+        .unwrap();
+        let message_gadget = UInt8::alloc_vec(cs.ns(|| "alloc message"), &message).unwrap();
+
+        let ciphertext_gadget = encryption_gadget
+            .check_encryption_gadget(cs.ns(|| "encrypt"), &randomness_gadget, &public_key_gadget, &message_gadget)
+            .unwrap();
+
+        let recovered_gadget = encryption_gadget
+            .check_decryption_gadget(cs.ns(|| "decrypt"), &private_key_gadget, &ciphertext_gadget)
+            .unwrap();
+
+        let recovered_message = recovered_gadget
+            .iter()
+            .map(|byte| byte.get_value().unwrap())
+            .collect::<Vec<_>>();
+
+        // The recovered buffer is fixed-length (sized by the ciphertext, not the message), so it
+        // is zero-padded past the real message rather than trimmed down to it.
+        assert_eq!(message, recovered_message[..message.len()]);
+        assert!(recovered_message[message.len()..].iter().all(|byte| *byte == 0));
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_check_public_key_gadget_windowed_matches_bit_by_bit() {
+        let rng = &mut test_rng();
+
+        let encryption =
+            ECIESPoseidonEncryption::<EdwardsParameters>::setup("test_check_public_key_gadget_windowed_matches_bit_by_bit");
+        let private_key = encryption.generate_private_key(rng);
+        let expected_public_key = encryption.generate_public_key(&private_key);
+
+        let mut cs = TestConstraintSystem::<Fq>::new();
+
+        let encryption_gadget = ECIESPoseidonEncryptionGadget::<EdwardsParameters, Fq>::alloc_constant(
+            cs.ns(|| "alloc encryption scheme"),
+            || Ok(encryption.clone()),
+        )
+        .unwrap();
+        let private_key_gadget = ECIESPoseidonEncryptionPrivateKeyGadget::<EdwardsParameters, Fq>::alloc(
+            cs.ns(|| "alloc private key"),
+            || Ok(private_key),
+        )
+        .unwrap();
+
+        let public_key_gadget = encryption_gadget
+            .check_public_key_gadget(cs.ns(|| "check_public_key_gadget"), &private_key_gadget)
+            .unwrap();
+        let windowed_public_key_gadget = encryption_gadget
+            .check_public_key_gadget_windowed(cs.ns(|| "check_public_key_gadget_windowed"), &private_key_gadget)
+            .unwrap();
+
+        windowed_public_key_gadget
+            .0
+            .enforce_equal(cs.ns(|| "windowed matches bit-by-bit"), &public_key_gadget.0)
+            .unwrap();
+
+        assert_eq!(expected_public_key, public_key_gadget.0.get_value().unwrap());
+        assert_eq!(expected_public_key, windowed_public_key_gadget.0.get_value().unwrap());
+        assert!(cs.is_satisfied());
+    }
 }
@@ -0,0 +1,481 @@
+// Copyright (C) 2019-2021 Aleo Systems Inc.
+// This file is part of the snarkVM library.
+
+// The snarkVM library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkVM library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkVM library. If not, see <https://www.gnu.org/licenses/>.
+
+use super::scalar::CanonicalScalarGadget;
+use crate::{AllocGadget, Boolean, ConditionalEqGadget, EqGadget, FieldGadget, GroupGadget, ToBytesGadget, UInt8};
+use itertools::Itertools;
+use snarkvm_algorithms::encryption::{GroupEncryption, GroupEncryptionPublicKey};
+use snarkvm_curves::{
+    templates::twisted_edwards_extended::{Affine as TEAffine, Projective as TEProjective},
+    AffineCurve,
+    ProjectiveCurve,
+    TwistedEdwardsParameters,
+};
+use snarkvm_fields::PrimeField;
+use snarkvm_r1cs::{ConstraintSystem, SynthesisError};
+use snarkvm_utilities::{borrow::Borrow, to_bytes_le, ToBytes};
+use std::marker::PhantomData;
+
+type TEAffineGadget<TE, F> = crate::curves::templates::twisted_edwards::AffineGadget<TE, F, crate::FpGadget<F>>;
+
+/// Group encryption private key gadget
+#[derive(Derivative)]
+#[derivative(
+    Clone(bound = "TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField"),
+    PartialEq(bound = "TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField"),
+    Eq(bound = "TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField"),
+    Debug(bound = "TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField")
+)]
+pub struct GroupEncryptionPrivateKeyGadget<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField>(
+    pub Vec<UInt8>,
+    PhantomData<TE>,
+    PhantomData<F>,
+);
+
+impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField> AllocGadget<TE::ScalarField, F>
+    for GroupEncryptionPrivateKeyGadget<TE, F>
+{
+    fn alloc_constant<
+        Fn: FnOnce() -> Result<T, SynthesisError>,
+        T: Borrow<TE::ScalarField>,
+        CS: ConstraintSystem<F>,
+    >(
+        _cs: CS,
+        value_gen: Fn,
+    ) -> Result<Self, SynthesisError> {
+        let private_key = to_bytes_le![value_gen()?.borrow()].unwrap();
+        Ok(GroupEncryptionPrivateKeyGadget(
+            UInt8::constant_vec(&private_key),
+            PhantomData,
+            PhantomData,
+        ))
+    }
+
+    fn alloc<Fn: FnOnce() -> Result<T, SynthesisError>, T: Borrow<TE::ScalarField>, CS: ConstraintSystem<F>>(
+        cs: CS,
+        value_gen: Fn,
+    ) -> Result<Self, SynthesisError> {
+        let private_key = to_bytes_le![value_gen()?.borrow()].unwrap();
+        Ok(GroupEncryptionPrivateKeyGadget(
+            UInt8::alloc_vec(cs, &private_key)?,
+            PhantomData,
+            PhantomData,
+        ))
+    }
+
+    fn alloc_input<Fn: FnOnce() -> Result<T, SynthesisError>, T: Borrow<TE::ScalarField>, CS: ConstraintSystem<F>>(
+        cs: CS,
+        value_gen: Fn,
+    ) -> Result<Self, SynthesisError> {
+        let private_key = to_bytes_le![value_gen()?.borrow()].unwrap();
+        Ok(GroupEncryptionPrivateKeyGadget(
+            UInt8::alloc_input_vec_le(cs, &private_key)?,
+            PhantomData,
+            PhantomData,
+        ))
+    }
+}
+
+impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField> ToBytesGadget<F>
+    for GroupEncryptionPrivateKeyGadget<TE, F>
+{
+    fn to_bytes<CS: ConstraintSystem<F>>(&self, mut cs: CS) -> Result<Vec<UInt8>, SynthesisError> {
+        self.0.to_bytes(&mut cs.ns(|| "to_bytes"))
+    }
+
+    fn to_bytes_strict<CS: ConstraintSystem<F>>(&self, mut cs: CS) -> Result<Vec<UInt8>, SynthesisError> {
+        self.0.to_bytes_strict(&mut cs.ns(|| "to_bytes_strict"))
+    }
+}
+
+/// Group encryption randomness gadget
+#[derive(Derivative)]
+#[derivative(
+    Clone(bound = "TE: TwistedEdwardsParameters"),
+    PartialEq(bound = "TE: TwistedEdwardsParameters"),
+    Eq(bound = "TE: TwistedEdwardsParameters"),
+    Debug(bound = "TE: TwistedEdwardsParameters")
+)]
+pub struct GroupEncryptionRandomnessGadget<TE: TwistedEdwardsParameters>(pub Vec<UInt8>, PhantomData<TE>);
+
+impl<TE: TwistedEdwardsParameters> AllocGadget<TE::ScalarField, TE::BaseField>
+    for GroupEncryptionRandomnessGadget<TE>
+where
+    TE::BaseField: PrimeField,
+{
+    fn alloc_constant<
+        Fn: FnOnce() -> Result<T, SynthesisError>,
+        T: Borrow<TE::ScalarField>,
+        CS: ConstraintSystem<TE::BaseField>,
+    >(
+        _cs: CS,
+        value_gen: Fn,
+    ) -> Result<Self, SynthesisError> {
+        let randomness = to_bytes_le![value_gen()?.borrow()].unwrap();
+        Ok(GroupEncryptionRandomnessGadget(
+            UInt8::constant_vec(&randomness),
+            PhantomData,
+        ))
+    }
+
+    fn alloc<
+        Fn: FnOnce() -> Result<T, SynthesisError>,
+        T: Borrow<TE::ScalarField>,
+        CS: ConstraintSystem<TE::BaseField>,
+    >(
+        cs: CS,
+        value_gen: Fn,
+    ) -> Result<Self, SynthesisError> {
+        let randomness = to_bytes_le![value_gen()?.borrow()].unwrap();
+        Ok(GroupEncryptionRandomnessGadget(
+            UInt8::alloc_vec(cs, &randomness)?,
+            PhantomData,
+        ))
+    }
+
+    fn alloc_input<
+        Fn: FnOnce() -> Result<T, SynthesisError>,
+        T: Borrow<TE::ScalarField>,
+        CS: ConstraintSystem<TE::BaseField>,
+    >(
+        cs: CS,
+        value_gen: Fn,
+    ) -> Result<Self, SynthesisError> {
+        let randomness = to_bytes_le![value_gen()?.borrow()].unwrap();
+        Ok(GroupEncryptionRandomnessGadget(
+            UInt8::alloc_input_vec_le(cs, &randomness)?,
+            PhantomData,
+        ))
+    }
+}
+
+/// Group encryption gadget
+#[derive(Derivative)]
+#[derivative(
+    Clone(bound = "TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField"),
+    PartialEq(bound = "TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField"),
+    Eq(bound = "TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField"),
+    Debug(bound = "TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField")
+)]
+pub struct GroupEncryptionGadget<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField> {
+    encryption: GroupEncryption<TE>,
+    f_phantom: PhantomData<F>,
+}
+
+impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField> AllocGadget<GroupEncryption<TE>, F>
+    for GroupEncryptionGadget<TE, F>
+{
+    fn alloc_constant<
+        Fn: FnOnce() -> Result<T, SynthesisError>,
+        T: Borrow<GroupEncryption<TE>>,
+        CS: ConstraintSystem<F>,
+    >(
+        _cs: CS,
+        value_gen: Fn,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            encryption: (*value_gen()?.borrow()).clone(),
+            f_phantom: PhantomData,
+        })
+    }
+
+    fn alloc<Fn: FnOnce() -> Result<T, SynthesisError>, T: Borrow<GroupEncryption<TE>>, CS: ConstraintSystem<F>>(
+        _cs: CS,
+        _value_gen: Fn,
+    ) -> Result<Self, SynthesisError> {
+        unimplemented!()
+    }
+
+    fn alloc_input<
+        Fn: FnOnce() -> Result<T, SynthesisError>,
+        T: Borrow<GroupEncryption<TE>>,
+        CS: ConstraintSystem<F>,
+    >(
+        _cs: CS,
+        _value_gen: Fn,
+    ) -> Result<Self, SynthesisError> {
+        unimplemented!()
+    }
+}
+
+/// Group encryption public key gadget
+#[derive(Derivative)]
+#[derivative(
+    Clone(bound = "TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField"),
+    PartialEq(bound = "TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField"),
+    Eq(bound = "TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField"),
+    Debug(bound = "TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField")
+)]
+pub struct GroupEncryptionPublicKeyGadget<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField>(
+    TEAffineGadget<TE, F>,
+);
+
+impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField> AllocGadget<GroupEncryptionPublicKey<TE>, F>
+    for GroupEncryptionPublicKeyGadget<TE, F>
+where
+    TEAffineGadget<TE, F>: GroupGadget<TEAffine<TE>, F>,
+{
+    fn alloc_constant<
+        Fn: FnOnce() -> Result<T, SynthesisError>,
+        T: Borrow<GroupEncryptionPublicKey<TE>>,
+        CS: ConstraintSystem<TE::BaseField>,
+    >(
+        cs: CS,
+        value_gen: Fn,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self(TEAffineGadget::<TE, F>::alloc_constant(cs, || {
+            Ok(value_gen()?.borrow().0.clone())
+        })?))
+    }
+
+    fn alloc<
+        Fn: FnOnce() -> Result<T, SynthesisError>,
+        T: Borrow<GroupEncryptionPublicKey<TE>>,
+        CS: ConstraintSystem<TE::BaseField>,
+    >(
+        cs: CS,
+        value_gen: Fn,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self(TEAffineGadget::<TE, F>::alloc(cs, || {
+            Ok(value_gen()?.borrow().0.clone())
+        })?))
+    }
+
+    fn alloc_input<
+        Fn: FnOnce() -> Result<T, SynthesisError>,
+        T: Borrow<GroupEncryptionPublicKey<TE>>,
+        CS: ConstraintSystem<TE::BaseField>,
+    >(
+        cs: CS,
+        value_gen: Fn,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self(TEAffineGadget::<TE, F>::alloc_input(cs, || {
+            Ok(value_gen()?.borrow().0.clone())
+        })?))
+    }
+}
+
+impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField> ConditionalEqGadget<F>
+    for GroupEncryptionPublicKeyGadget<TE, F>
+{
+    fn conditional_enforce_equal<CS: ConstraintSystem<F>>(
+        &self,
+        cs: CS,
+        other: &Self,
+        condition: &Boolean,
+    ) -> Result<(), SynthesisError> {
+        self.0.conditional_enforce_equal(cs, &other.0, condition)
+    }
+
+    fn cost() -> usize {
+        <TEAffineGadget<TE, F> as ConditionalEqGadget<F>>::cost()
+    }
+}
+
+impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField> ToBytesGadget<F>
+    for GroupEncryptionPublicKeyGadget<TE, F>
+{
+    fn to_bytes<CS: ConstraintSystem<F>>(&self, cs: CS) -> Result<Vec<UInt8>, SynthesisError> {
+        self.0.x.to_bytes(cs)
+    }
+
+    fn to_bytes_strict<CS: ConstraintSystem<F>>(&self, cs: CS) -> Result<Vec<UInt8>, SynthesisError> {
+        self.0.x.to_bytes_strict(cs)
+    }
+}
+
+impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField> EqGadget<F> for GroupEncryptionPublicKeyGadget<TE, F> {}
+
+impl<TE: TwistedEdwardsParameters<BaseField = F>, F: PrimeField> GroupEncryptionGadget<TE, F>
+where
+    TEAffineGadget<TE, F>: GroupGadget<TEAffine<TE>, F>,
+{
+    /// Derives the public key `private_key * generator` in-circuit, the same way
+    /// `ECIESPoseidonEncryptionGadget::check_public_key_gadget` derives its public key.
+    pub fn check_public_key_gadget<CS: ConstraintSystem<TE::BaseField>>(
+        &self,
+        mut cs: CS,
+        private_key: &GroupEncryptionPrivateKeyGadget<TE, F>,
+    ) -> Result<GroupEncryptionPublicKeyGadget<TE, F>, SynthesisError> {
+        // Range-check the private key against `TE::ScalarField`'s modulus before it is ever
+        // multiplied against the generator, the same way `ECIESPoseidonEncryptionGadget` does.
+        let private_key_bits =
+            CanonicalScalarGadget::<TE, F>::alloc(cs.ns(|| "canonicalize private key"), &private_key.0)?.bits;
+        let mut public_key = <TEAffineGadget<TE, F> as GroupGadget<TEAffine<TE>, F>>::zero(cs.ns(|| "zero"))?;
+
+        let num_powers = private_key_bits.len();
+        let generator_powers: Vec<TEAffine<TE>> = {
+            let mut generator_powers = Vec::new();
+            let mut generator = self.encryption.generator.into_projective();
+            for _ in 0..num_powers {
+                generator_powers.push(generator.clone());
+                generator.double_in_place();
+            }
+            TEProjective::<TE>::batch_normalization(&mut generator_powers);
+            generator_powers.into_iter().map(|v| v.into()).collect()
+        };
+
+        public_key.scalar_multiplication(
+            cs.ns(|| "check_public_key_gadget"),
+            private_key_bits.iter().zip_eq(&generator_powers),
+        )?;
+
+        Ok(GroupEncryptionPublicKeyGadget(public_key))
+    }
+
+    /// Encrypts `message` using additive ElGamal-style group encryption. The message elements
+    /// are taken as group elements that are already on the curve; this function does not encode
+    /// field elements onto the curve. The `i`-th message element (counting from zero) is
+    /// blinded by the shared-secret group element `(i + 1) * ecdh_value`, where
+    /// `ecdh_value = randomness * public_key`, and the output starts with the randomness
+    /// element `randomness * generator`, followed by one blinded element per message element. Unlike `ECIESPoseidonEncryptionGadget`, the
+    /// result is a vector of group elements rather than masked field bytes, which keeps the
+    /// ciphertext homomorphic: re-randomizing or additively combining plaintexts can be done by
+    /// the same group operations used here.
+    pub fn check_encryption_gadget<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+        randomness: &GroupEncryptionRandomnessGadget<TE>,
+        public_key: &GroupEncryptionPublicKeyGadget<TE, F>,
+        message: &[TEAffineGadget<TE, F>],
+    ) -> Result<Vec<TEAffineGadget<TE, F>>, SynthesisError> {
+        let affine_zero: TEAffineGadget<TE, F> =
+            <TEAffineGadget<TE, F> as GroupGadget<TEAffine<TE>, F>>::zero(cs.ns(|| "affine zero"))?;
+
+        // Compute the shared-secret ECDH value. The randomness scalar is range-checked for the
+        // same reason the private key is in `check_public_key_gadget`.
+        let randomness_bits =
+            CanonicalScalarGadget::<TE, F>::alloc(cs.ns(|| "canonicalize randomness"), &randomness.0)?.bits;
+        let ecdh_value = <TEAffineGadget<TE, F> as GroupGadget<TEAffine<TE>, F>>::mul_bits(
+            &public_key.0,
+            cs.ns(|| "compute_ecdh_value"),
+            &affine_zero,
+            randomness_bits.iter().copied(),
+        )?;
+
+        // Blind each encoded message element by its own multiple of the shared secret, so that
+        // swapping ciphertext elements changes what they decrypt to, and put the
+        // generator-scaled randomness element first so the decryptor can recompute the same blinds.
+        let generator_gadget = TEAffineGadget::<TE, F>::alloc_constant(cs.ns(|| "alloc generator"), || {
+            Ok(self.encryption.generator.clone())
+        })?;
+        let randomness_elem = <TEAffineGadget<TE, F> as GroupGadget<TEAffine<TE>, F>>::mul_bits(
+            &generator_gadget,
+            cs.ns(|| "compute the randomness element"),
+            &affine_zero,
+            randomness_bits.iter().copied(),
+        )?;
+
+        let mut ciphertext = Vec::with_capacity(message.len() + 1);
+        ciphertext.push(randomness_elem);
+
+        let mut blind = ecdh_value;
+        for (i, encoded_message) in message.iter().enumerate() {
+            let masked = encoded_message.add(cs.ns(|| format!("mask message element {}", i)), &blind)?;
+            ciphertext.push(masked);
+
+            if i + 1 < message.len() {
+                blind = blind.add(cs.ns(|| format!("advance shared secret {}", i)), &ecdh_value)?;
+            }
+        }
+
+        Ok(ciphertext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm_algorithms::EncryptionScheme;
+    use snarkvm_curves::edwards_bls12::{EdwardsParameters, Fq, Fr};
+    use snarkvm_r1cs::TestConstraintSystem;
+    use snarkvm_utilities::test_rng;
+
+    #[test]
+    fn test_check_encryption_gadget_matches_native_computation() {
+        let rng = &mut test_rng();
+
+        let encryption =
+            GroupEncryption::<EdwardsParameters>::setup("test_check_encryption_gadget_matches_native_computation");
+        let private_key = encryption.generate_private_key(rng);
+        let public_key = encryption.generate_public_key(&private_key);
+        let randomness = encryption.generate_randomness(rng);
+
+        let message_points: Vec<TEAffine<EdwardsParameters>> = (1u64..=3)
+            .map(|i| encryption.generator.mul(Fr::from(i)).into())
+            .collect();
+
+        // Replicate the circuit's math natively, as the known answer to check the gadget against.
+        let ecdh_value: TEAffine<EdwardsParameters> = public_key.0.mul(randomness).into();
+        let randomness_elem: TEAffine<EdwardsParameters> = encryption.generator.mul(randomness).into();
+        let mut expected_ciphertext = vec![randomness_elem];
+        let mut blind = ecdh_value;
+        for point in &message_points {
+            expected_ciphertext.push((*point + blind).into());
+            blind = (blind + ecdh_value).into();
+        }
+
+        let mut cs = TestConstraintSystem::<Fq>::new();
+
+        let encryption_gadget =
+            GroupEncryptionGadget::<EdwardsParameters, Fq>::alloc_constant(cs.ns(|| "alloc encryption scheme"), || {
+                Ok(encryption.clone())
+            })
+            .unwrap();
+        let private_key_gadget = GroupEncryptionPrivateKeyGadget::<EdwardsParameters, Fq>::alloc(
+            cs.ns(|| "alloc private key"),
+            || Ok(private_key),
+        )
+        .unwrap();
+        let randomness_gadget = GroupEncryptionRandomnessGadget::<EdwardsParameters>::alloc(
+            cs.ns(|| "alloc randomness"),
+            || Ok(randomness),
+        )
+        .unwrap();
+        let public_key_gadget = GroupEncryptionPublicKeyGadget::<EdwardsParameters, Fq>::alloc(
+            cs.ns(|| "alloc public key"),
+            || Ok(public_key),
+        )
+        .unwrap();
+        let message_gadget = message_points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| {
+                TEAffineGadget::<EdwardsParameters, Fq>::alloc(cs.ns(|| format!("alloc message {}", i)), || Ok(*point))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let derived_public_key_gadget = encryption_gadget
+            .check_public_key_gadget(cs.ns(|| "check_public_key_gadget"), &private_key_gadget)
+            .unwrap();
+        assert_eq!(public_key.0, derived_public_key_gadget.0.get_value().unwrap());
+
+        let ciphertext_gadget = encryption_gadget
+            .check_encryption_gadget(
+                cs.ns(|| "check_encryption_gadget"),
+                &randomness_gadget,
+                &public_key_gadget,
+                &message_gadget,
+            )
+            .unwrap();
+
+        let ciphertext = ciphertext_gadget.iter().map(|elem| elem.get_value().unwrap()).collect::<Vec<_>>();
+
+        assert_eq!(expected_ciphertext, ciphertext);
+        assert!(cs.is_satisfied());
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright (C) 2019-2021 Aleo Systems Inc.
+// This file is part of the snarkVM library.
+
+// The snarkVM library is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// The snarkVM library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with the snarkVM library. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{Boolean, EqGadget, UInt8};
+use snarkvm_curves::TwistedEdwardsParameters;
+use snarkvm_fields::{FieldParameters, PrimeField};
+use snarkvm_r1cs::{ConstraintSystem, SynthesisError};
+use snarkvm_utilities::Integer;
+use std::marker::PhantomData;
+
+/// A canonical, range-checked representation of a `TE::ScalarField` element, witnessed as
+/// little-endian bits over the base field `F`.
+///
+/// `ECIESPoseidonEncryptionPrivateKeyGadget` and `ECIESPoseidonEncryptionRandomnessGadget` only
+/// store raw bytes, so naively flattening them with `to_bits_le()` lets multiple byte patterns
+/// collapse onto the same scalar modulo `TE::ScalarField`'s modulus, which is a malleability gap.
+/// This gadget takes the bits of those bytes and enforces `scalar < modulus` by comparing them,
+/// from the most significant bit down, against the constant bits of the modulus, so only
+/// canonical scalars are ever accepted. The scalar is not split into limbs.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "TE: TwistedEdwardsParameters"), Debug(bound = "TE: TwistedEdwardsParameters"))]
+pub struct CanonicalScalarGadget<TE: TwistedEdwardsParameters, F: PrimeField> {
+    /// The little-endian bits of the canonical scalar.
+    pub bits: Vec<Boolean>,
+    _te: PhantomData<TE>,
+    _f: PhantomData<F>,
+}
+
+impl<TE: TwistedEdwardsParameters, F: PrimeField> CanonicalScalarGadget<TE, F> {
+    /// Witnesses `bytes` as a `TE::ScalarField` element and enforces that the resulting bit
+    /// pattern is strictly smaller than the scalar field modulus.
+    pub fn alloc<CS: ConstraintSystem<F>>(mut cs: CS, bytes: &[UInt8]) -> Result<Self, SynthesisError> {
+        let mut scalar_bits = bytes.iter().flat_map(|byte| byte.to_bits_le()).collect::<Vec<_>>();
+
+        let mut modulus_bits = <TE::ScalarField as PrimeField>::Parameters::MODULUS
+            .to_bits_le()
+            .into_iter()
+            .map(Boolean::constant)
+            .collect::<Vec<_>>();
+
+        // Zero-pad whichever side is shorter so the borrow chain below walks matching bit
+        // positions; the byte encoding is always at least as wide as the modulus.
+        let len = scalar_bits.len().max(modulus_bits.len());
+        scalar_bits.resize(len, Boolean::constant(false));
+        modulus_bits.resize(len, Boolean::constant(false));
+
+        // Bitwise comparison: walk from the most significant bit down, tracking whether the
+        // prefix seen so far is still tied (`is_equal_so_far`) and whether a strictly-smaller bit
+        // has already been found (`is_less`).
+        let mut is_equal_so_far = Boolean::constant(true);
+        let mut is_less = Boolean::constant(false);
+        for i in (0..len).rev() {
+            let scalar_bit = &scalar_bits[i];
+            match &modulus_bits[i] {
+                Boolean::Constant(true) => {
+                    let newly_less = Boolean::and(
+                        cs.ns(|| format!("newly less at bit {}", i)),
+                        &is_equal_so_far,
+                        &scalar_bit.not(),
+                    )?;
+                    is_less = Boolean::or(cs.ns(|| format!("accumulate less at bit {}", i)), &is_less, &newly_less)?;
+                    is_equal_so_far = Boolean::and(
+                        cs.ns(|| format!("accumulate equal at bit {}", i)),
+                        &is_equal_so_far,
+                        scalar_bit,
+                    )?;
+                }
+                Boolean::Constant(false) => {
+                    is_equal_so_far = Boolean::and(
+                        cs.ns(|| format!("accumulate equal at bit {}", i)),
+                        &is_equal_so_far,
+                        &scalar_bit.not(),
+                    )?;
+                }
+                _ => unreachable!("the scalar field modulus is a hardcoded public constant"),
+            }
+        }
+        is_less.enforce_equal(cs.ns(|| "enforce scalar is canonical"), &Boolean::constant(true))?;
+
+        Ok(Self {
+            bits: scalar_bits,
+            _te: PhantomData,
+            _f: PhantomData,
+        })
+    }
+}